@@ -1,7 +1,8 @@
 use attohttpc::get;
 use cargo_lock::{Lockfile, Name, Package};
+use rayon::prelude::*;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     str::FromStr,
 };
 use url::Url;
@@ -16,9 +17,18 @@ struct Spec {
 
 #[derive(Default)]
 struct Args {
-    spec_a: Spec,
-    spec_b: Spec,
+    specs: Vec<Spec>,
     verbose: bool,
+    semver_compatible: bool,
+    by_name_only: bool,
+    format: OutputFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum OutputFormat {
+    #[default]
+    Text,
+    Json,
 }
 
 fn comma_separated_list(s: &Option<String>) -> Vec<String> {
@@ -33,38 +43,57 @@ impl Args {
     fn parse() -> Result<Self, String> {
         let mut args = Args::default();
         let flags = xflags::parse_or_exit! {
-            /// Limit first lockfile to package tree rooted at hash (git commit or crate checksum)
-            optional --pkg-hash-a hash_a: String
-            /// Limit second lockfile to package tree rooted at hash (git commit or crate checksum)
-            optional --pkg-hash-b hash_b: String
-            /// Limit first lockfile to package tree rooted at package name
-            optional --pkg-name-a name_a: String
-            /// Limit second lockfile to package tree rooted at package name
-            optional --pkg-name-b name_b: String
-            /// Comma-separated list of packages to exclude from first lockfile
-            optional --exclude-pkg-a exclude_a: String
-            /// Comma-separated list of packages to exclude from second lockfile
-            optional --exclude-pkg-b exclude_b: String
-            /// First lockfile (URL or path)
-            required lockfile_a: String
-            /// Second lockfile (URL or path)
-            required lockfile_b: String
+            /// Limit a lockfile to the package tree rooted at hash (git commit or crate
+            /// checksum); may be repeated once per lockfile argument, in order
+            repeated --pkg-hash pkg_hash: String
+            /// Limit a lockfile to the package tree rooted at package name; may be
+            /// repeated once per lockfile argument, in order
+            repeated --pkg-name pkg_name: String
+            /// Comma-separated list of packages to exclude from a lockfile; may be
+            /// repeated once per lockfile argument, in order
+            repeated --exclude-pkg exclude_pkg: String
+            /// Lockfile (URL or path); pass two or more
+            repeated lockfile: String
             /// Print more details while running
             optional --verbose
+            /// Treat versions as matching when they are semver-compatible (same
+            /// caret-compatibility class) rather than requiring byte-identical versions
+            optional --semver-compatible
+            /// Identify packages by name alone, ignoring their source (registry, git,
+            /// or path); restores the pre-source-aware behavior
+            optional --by-name-only
+            /// Output format: "text" (default) or "json"
+            optional --format format: String
         };
-        args.spec_a.pkg_hash = flags.pkg_hash_a;
-        args.spec_b.pkg_hash = flags.pkg_hash_b;
-        args.spec_a.pkg_name = flags.pkg_name_a;
-        args.spec_b.pkg_name = flags.pkg_name_b;
-        args.spec_a.exclude_pkgs = comma_separated_list(&flags.exclude_pkg_a)
-            .into_iter()
-            .collect();
-        args.spec_b.exclude_pkgs = comma_separated_list(&flags.exclude_pkg_b)
+        if flags.lockfile.len() < 2 {
+            return Err("at least two lockfiles are required".to_string());
+        }
+        args.specs = flags
+            .lockfile
             .into_iter()
+            .enumerate()
+            .map(|(i, src)| Spec {
+                src,
+                pkg_name: flags.pkg_name.get(i).cloned(),
+                pkg_hash: flags.pkg_hash.get(i).cloned(),
+                exclude_pkgs: comma_separated_list(&flags.exclude_pkg.get(i).cloned())
+                    .into_iter()
+                    .collect(),
+            })
             .collect();
-        args.spec_a.src = flags.lockfile_a;
-        args.spec_b.src = flags.lockfile_b;
         args.verbose = flags.verbose;
+        args.semver_compatible = flags.semver_compatible;
+        args.by_name_only = flags.by_name_only;
+        args.format = match flags.format.as_deref() {
+            None | Some("text") => OutputFormat::Text,
+            Some("json") => OutputFormat::Json,
+            Some(other) => {
+                return Err(format!(
+                    "unknown --format {:?}, expected text or json",
+                    other
+                ))
+            }
+        };
         Ok(args)
     }
 }
@@ -75,12 +104,48 @@ enum Phase {
     NameAndVersionIntersection,
 }
 
+/// Identity under which a package is tracked: its name plus, unless
+/// `--by-name-only` is given, where it came from (registry URL, git URL+ref,
+/// or local path). Without `--by-name-only`, a `None` source means a path
+/// dependency with no source; with it, the source is always `None`.
+type PkgKey = (Name, Option<String>);
+
+fn pkg_key(package: &Package, by_name_only: bool) -> PkgKey {
+    let source = if by_name_only {
+        None
+    } else {
+        package.source.as_ref().map(|s| s.to_string())
+    };
+    (package.name.clone(), source)
+}
+
+/// All tracked variants of `name` in `state` (normally one, but a name can
+/// resolve to more than one source within a single lockfile). Iteration order
+/// is deterministic: `BTreeMap` order over `(Name, Option<String>)`.
+fn entries_for_name<'a>(
+    state: &'a State,
+    name: &Name,
+) -> Vec<(&'a PkgKey, &'a Package, &'a Vec<PathEntry>)> {
+    state
+        .packages
+        .iter()
+        .filter(|(key, _)| &key.0 == name)
+        .map(|(key, (pkg, path))| (key, pkg, path))
+        .collect()
+}
+
 struct State {
     spec: Spec,
     lockfile: Lockfile,
-    packages: BTreeMap<Name, (Package, Vec<Package>)>,
+    /// Index from package name to every package of that name in the lockfile,
+    /// built once so `add_all_dependencies_recursive` doesn't linear-scan the
+    /// whole lockfile for every dependency edge.
+    by_name: HashMap<Name, Vec<Package>>,
+    packages: BTreeMap<PkgKey, (Package, Vec<PathEntry>)>,
     phase: Phase,
     verbose: bool,
+    semver_compatible: bool,
+    by_name_only: bool,
 }
 
 fn load_lockfile(src: &str) -> Result<Lockfile, String> {
@@ -130,33 +195,172 @@ fn package_matches_hash(pkg: &cargo_lock::Package, hash: &str) -> bool {
     false
 }
 
-fn path_to_str(path: &Vec<Package>) -> String {
+/// One hop of a dependency path: the package found at that hop, and the
+/// semver requirement (from the parent's `Dependency` edge) that selected it.
+/// `None` for the root of the path, which no requirement pulled in.
+type PathEntry = (Package, Option<String>);
+
+fn path_to_str(path: &[PathEntry]) -> String {
     path.iter()
-        .map(|p| format!("{}@{}", p.name, p.version))
+        .map(|(p, _)| format!("{}@{}", p.name, p.version))
         .collect::<Vec<_>>()
         .join(" -> ")
 }
 
+/// The requirement that pulled the last package in `path` into the tree, if any.
+fn path_last_requirement(path: &[PathEntry]) -> Option<&str> {
+    path.last().and_then(|(_, req)| req.as_deref())
+}
+
+/// Render a dependency path as a JSON array of per-hop objects
+/// `{"name", "version", "req"}`, so a consumer can walk the chain instead of
+/// re-parsing a joined string.
+fn path_to_json(path: &[PathEntry]) -> String {
+    let hops = path
+        .iter()
+        .map(|(pkg, req)| {
+            format!(
+                "{{\"name\":\"{}\",\"version\":\"{}\",\"req\":{}}}",
+                json_escape(pkg.name.as_str()),
+                json_escape(&pkg.version.to_string()),
+                match req {
+                    Some(req) => format!("\"{}\"", json_escape(req)),
+                    None => "null".to_string(),
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("[{}]", hops)
+}
+
+/// JSON for one lockfile's view of a name: a set of parallel arrays that
+/// normally hold a single element, but hold more than one when that
+/// lockfile resolves the name to more than one source.
+fn entry_json(entries: &[(&PkgKey, &Package, &Vec<PathEntry>)]) -> String {
+    let versions = entries
+        .iter()
+        .map(|(_, pkg, _)| format!("\"{}\"", json_escape(&pkg.version.to_string())))
+        .collect::<Vec<_>>()
+        .join(",");
+    let sources = entries
+        .iter()
+        .map(|(key, _, _)| match &key.1 {
+            Some(src) => format!("\"{}\"", json_escape(src)),
+            None => "null".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let paths = entries
+        .iter()
+        .map(|(_, _, path)| path_to_json(path))
+        .collect::<Vec<_>>()
+        .join(",");
+    let requirements = entries
+        .iter()
+        .map(|(_, _, path)| match path_last_requirement(path) {
+            Some(req) => format!("\"{}\"", json_escape(req)),
+            None => "null".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "{{\"versions\":[{}],\"sources\":[{}],\"paths\":[{}],\"requirements\":[{}]}}",
+        versions, sources, paths, requirements
+    )
+}
+
+fn json_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Are `a` and `b` in the same Cargo caret-compatibility class? For versions
+/// `>= 1.0.0` this means equal majors; for `0.x.y` (x != 0) it means equal
+/// minors; for `0.0.z` it means the versions are fully equal.
+fn versions_compatible(a: &cargo_lock::semver::Version, b: &cargo_lock::semver::Version) -> bool {
+    if a.major != b.major {
+        return false;
+    }
+    if a.major >= 1 {
+        true
+    } else if a.minor != 0 {
+        a.minor == b.minor
+    } else {
+        a == b
+    }
+}
+
+/// A representative version for a requirement's lower bound: the leading
+/// comparator's major/minor/patch, defaulting missing fields to 0 (the same
+/// way a caret requirement treats them). `None` if the requirement has no
+/// comparators or fails to parse.
+fn requirement_anchor(req: &str) -> Option<cargo_lock::semver::Version> {
+    let req = req.parse::<cargo_lock::semver::VersionReq>().ok()?;
+    let cmp = req.comparators.first()?;
+    Some(cargo_lock::semver::Version::new(
+        cmp.major,
+        cmp.minor.unwrap_or(0),
+        cmp.patch.unwrap_or(0),
+    ))
+}
+
+/// Are the two requirement strings in the same caret-compatibility class -
+/// e.g. `^1.0` and `^1.2` - so that a version satisfying one would plausibly
+/// satisfy the other, as opposed to requirements that genuinely diverge?
+fn requirements_compatible(a: &str, b: &str) -> bool {
+    match (requirement_anchor(a), requirement_anchor(b)) {
+        (Some(a), Some(b)) => versions_compatible(&a, &b),
+        _ => false,
+    }
+}
+
 impl State {
-    fn new(spec: Spec, verbose: bool) -> Result<Self, String> {
+    fn new(
+        spec: Spec,
+        verbose: bool,
+        semver_compatible: bool,
+        by_name_only: bool,
+    ) -> Result<Self, String> {
         let lockfile = load_lockfile(&spec.src)?;
+        let mut by_name: HashMap<Name, Vec<Package>> = HashMap::new();
+        for package in lockfile.packages.iter() {
+            by_name
+                .entry(package.name.clone())
+                .or_default()
+                .push(package.clone());
+        }
         Ok(State {
             spec,
             lockfile,
+            by_name,
             phase: Phase::NameIntersection,
             packages: BTreeMap::new(),
             verbose,
+            semver_compatible,
+            by_name_only,
         })
     }
 
     fn try_insert_package(
         &mut self,
         package: &Package,
-        path: &Vec<Package>,
+        path: &Vec<PathEntry>,
     ) -> Result<bool, String> {
-        if let Some(existing) = self.packages.get(&package.name) {
+        let key = pkg_key(package, self.by_name_only);
+        if let Some(existing) = self.packages.get(&key) {
             if self.phase == Phase::NameAndVersionIntersection
                 && existing.0.version != package.version
+                && !(self.semver_compatible
+                    && versions_compatible(&existing.0.version, &package.version))
             {
                 return Err(format!(
                     "Package {} has multiple versions in lockfile {}: {} and {}, path: {}",
@@ -170,13 +374,14 @@ impl State {
             Ok(false)
         } else {
             if self.verbose {
-                println!(
+                // Verbose tracing must not land on stdout: `--format json` writes a
+                // single JSON object there, and interleaved text would corrupt it.
+                eprintln!(
                     "found {} {} {}",
                     self.spec.src, package.name, package.version
                 );
             }
-            self.packages
-                .insert(package.name.clone(), (package.clone(), path.clone()));
+            self.packages.insert(key, (package.clone(), path.clone()));
             Ok(true)
         }
     }
@@ -184,20 +389,19 @@ impl State {
     fn add_all_dependencies_recursive(
         &mut self,
         package: &Package,
-        path: &mut Vec<Package>,
+        path: &mut Vec<PathEntry>,
     ) -> Result<(), String> {
         for dep in package.dependencies.iter() {
             if self.spec.exclude_pkgs.contains(dep.name.as_str()) {
                 continue;
             }
             let dep_pkg = self
-                .lockfile
-                .packages
-                .iter()
-                .cloned()
-                .find(|p| dep.matches(p));
+                .by_name
+                .get(&dep.name)
+                .and_then(|candidates| candidates.iter().find(|p| dep.matches(p)))
+                .cloned();
             if let Some(dep_pkg) = dep_pkg {
-                path.push(dep_pkg.clone());
+                path.push((dep_pkg.clone(), Some(dep.req.to_string())));
                 if self.try_insert_package(&dep_pkg, &path)? {
                     self.add_all_dependencies_recursive(&dep_pkg, path)?;
                 }
@@ -224,9 +428,11 @@ impl State {
                 }
             }
 
-            let mut path = vec![package.clone()];
-            self.packages
-                .insert(package.name.clone(), (package.clone(), path.clone()));
+            let mut path = vec![(package.clone(), None)];
+            self.packages.insert(
+                pkg_key(package, self.by_name_only),
+                (package.clone(), path.clone()),
+            );
             self.add_all_dependencies_recursive(package, &mut path)?;
             return Ok(());
         }
@@ -246,7 +452,7 @@ impl State {
             .collect::<Vec<_>>();
         let mut path = Vec::new();
         for package in all_packages {
-            path.push(package.clone());
+            path.push((package.clone(), None));
             self.try_insert_package(&package, &mut path)?;
             path.pop();
         }
@@ -263,76 +469,179 @@ impl State {
 }
 
 struct Program {
-    state_a: State,
-    state_b: State,
+    states: Vec<State>,
+    format: OutputFormat,
 }
 
 impl Program {
     fn new() -> Result<Self, String> {
         let args = Args::parse()?;
-        let state_a = State::new(args.spec_a, args.verbose)?;
-        let state_b = State::new(args.spec_b, args.verbose)?;
-        Ok(Program { state_a, state_b })
+        let states = args
+            .specs
+            .into_par_iter()
+            .map(|spec| {
+                State::new(
+                    spec,
+                    args.verbose,
+                    args.semver_compatible,
+                    args.by_name_only,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Program {
+            states,
+            format: args.format,
+        })
     }
 
     fn add_packages_and_calculate_intesection(&mut self) -> Result<BTreeSet<Name>, String> {
-        self.state_a.add_packages()?;
-        self.state_b.add_packages()?;
-        let package_names_a = self.state_a.packages.keys().collect::<BTreeSet<_>>();
-        let package_names_b = self.state_b.packages.keys().collect::<BTreeSet<_>>();
-        let intersection = package_names_a
-            .intersection(&package_names_b)
-            .map(|x| (*x).clone())
-            .collect::<BTreeSet<Name>>();
-        println!("{} packages in lockfile A", package_names_a.len());
-        println!("{} packages in lockfile B", package_names_b.len());
-        println!("{} packages in common", intersection.len());
+        self.states
+            .par_iter_mut()
+            .try_for_each(|state| state.add_packages())?;
+        // Intersect on name alone: a name present (under any source) in every
+        // lockfile belongs in the common set, even if the source differs
+        // between lockfiles - that divergence is reported, not hidden.
+        let mut name_sets = self.states.iter().map(|state| {
+            state
+                .packages
+                .keys()
+                .map(|(name, _)| name.clone())
+                .collect::<BTreeSet<Name>>()
+        });
+        let first = name_sets.next().ok_or("no lockfiles given")?;
+        let intersection = name_sets.fold(first, |acc, names| {
+            acc.intersection(&names).cloned().collect()
+        });
+        if self.format == OutputFormat::Text {
+            for (i, state) in self.states.iter().enumerate() {
+                println!("{} packages in lockfile {}", state.packages.len(), i);
+            }
+            println!("{} packages in common", intersection.len());
+        }
         Ok(intersection)
     }
 
     fn run(&mut self) -> Result<(), String> {
         let first_pass_intersection = self.add_packages_and_calculate_intesection()?;
-        println!("excluding packages outside intersection and recalculating");
-        let mut excluded_a = 0;
-        let mut excluded_b = 0;
-        for pkg in self.state_a.packages.keys().cloned().collect::<Vec<_>>() {
-            if !first_pass_intersection.contains(&pkg) {
-                excluded_a += 1;
-                self.state_a
-                    .spec
-                    .exclude_pkgs
-                    .insert(pkg.as_str().to_string());
-            }
+        let lockfile_counts = self
+            .states
+            .iter()
+            .map(|state| state.packages.len())
+            .collect::<Vec<_>>();
+        if self.format == OutputFormat::Text {
+            println!("excluding packages outside intersection and recalculating");
         }
-        for pkg in self.state_b.packages.keys().cloned().collect::<Vec<_>>() {
-            if !first_pass_intersection.contains(&pkg) {
-                excluded_b += 1;
-                self.state_b
-                    .spec
-                    .exclude_pkgs
-                    .insert(pkg.as_str().to_string());
+        for (i, state) in self.states.iter_mut().enumerate() {
+            let mut excluded = 0;
+            for key in state.packages.keys().cloned().collect::<Vec<_>>() {
+                if !first_pass_intersection.contains(&key.0) {
+                    excluded += 1;
+                    state.spec.exclude_pkgs.insert(key.0.as_str().to_string());
+                }
             }
+            if self.format == OutputFormat::Text {
+                println!("excluded {} more packages from lockfile {}", excluded, i);
+            }
+            state.phase = Phase::NameAndVersionIntersection;
+            state.packages.clear();
         }
-        println!("excluded {} more packages from lockfile A", excluded_a);
-        println!("excluded {} more packages from lockfile B", excluded_b);
-        self.state_a.phase = Phase::NameAndVersionIntersection;
-        self.state_b.phase = Phase::NameAndVersionIntersection;
-        self.state_a.packages.clear();
-        self.state_b.packages.clear();
         let intersection = self.add_packages_and_calculate_intesection()?;
 
+        match self.format {
+            OutputFormat::Text => self.report_text(&intersection),
+            OutputFormat::Json => self.report_json(&intersection, &lockfile_counts),
+        }
+    }
+
+    fn report_text(&self, intersection: &BTreeSet<Name>) -> Result<(), String> {
         let mut all_ok = true;
+        let semver_compatible = self.states[0].semver_compatible;
         for name in intersection.iter() {
-            let (pkg_a, path_a) = self.state_a.packages.get(name).unwrap();
-            let (pkg_b, path_b) = self.state_b.packages.get(name).unwrap();
-            if pkg_a.version == pkg_b.version {
-                if self.state_a.verbose {
-                    println!("SAME {} {}", name, pkg_a.version);
+            // One slot per lockfile, but a slot can hold more than one entry
+            // when that lockfile resolves `name` to more than one source -
+            // keep every variant instead of arbitrarily picking the first.
+            let per_lockfile = self
+                .states
+                .iter()
+                .map(|state| entries_for_name(state, name))
+                .collect::<Vec<_>>();
+            let multi_source = per_lockfile.iter().any(|entries| entries.len() > 1);
+            let canonical = per_lockfile
+                .iter()
+                .map(|entries| entries[0])
+                .collect::<Vec<_>>();
+            let first_version = &canonical[0].1.version;
+            let first_source = &canonical[0].0 .1;
+            let same_source =
+                !multi_source && canonical.iter().all(|(key, _, _)| &key.1 == first_source);
+            let same_version = canonical.iter().all(|(_, pkg, _)| {
+                &pkg.version == first_version
+                    || (semver_compatible && versions_compatible(&pkg.version, first_version))
+            });
+            if same_source && same_version {
+                if self.states[0].verbose {
+                    println!("SAME {} {}", name, first_version);
                 }
             } else {
-                println!("DIFFERENT {} {} vs. {}", name, pkg_a.version, pkg_b.version);
-                println!("  path A: {}", path_to_str(path_a));
-                println!("  path B: {}", path_to_str(path_b));
+                let versions = canonical
+                    .iter()
+                    .map(|(_, pkg, _)| pkg.version.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" / ");
+                println!("DIFFERENT {} {}", name, versions);
+                if multi_source {
+                    println!(
+                        "  note: {} resolves to more than one source within a single lockfile",
+                        name
+                    );
+                }
+                if !same_source {
+                    let sources = canonical
+                        .iter()
+                        .map(|(key, _, _)| key.1.clone().unwrap_or_else(|| "<none>".to_string()))
+                        .collect::<Vec<_>>()
+                        .join(" / ");
+                    println!("  sources: {}", sources);
+                }
+                for (i, entries) in per_lockfile.iter().enumerate() {
+                    for (j, (_, _, path)) in entries.iter().enumerate() {
+                        let label = if entries.len() > 1 {
+                            format!("{}.{}", i, j)
+                        } else {
+                            i.to_string()
+                        };
+                        match path_last_requirement(path) {
+                            Some(req) => {
+                                println!("  path {}: {} (req {:?})", label, path_to_str(path), req)
+                            }
+                            None => println!("  path {}: {}", label, path_to_str(path)),
+                        }
+                    }
+                }
+                if same_source {
+                    let reqs = canonical
+                        .iter()
+                        .map(|(_, _, path)| path_last_requirement(path))
+                        .collect::<Vec<_>>();
+                    if let Some(first_req) = reqs[0] {
+                        let all_compatible = reqs.iter().all(|r| match r {
+                            Some(req) => requirements_compatible(first_req, req),
+                            None => false,
+                        });
+                        if all_compatible {
+                            let reqs_str = reqs
+                                .iter()
+                                .map(|r| r.unwrap_or("?"))
+                                .collect::<Vec<_>>()
+                                .join(" / ");
+                            println!(
+                                "  hint: every lockfile resolved {} via compatible requirements \
+                                 ({}); only the lock differs, try `cargo update -p {}`",
+                                name, reqs_str, name
+                            );
+                        }
+                    }
+                }
                 all_ok = false;
             }
         }
@@ -343,6 +652,71 @@ impl Program {
             Err("Some packages have different versions".to_string())
         }
     }
+
+    fn report_json(
+        &self,
+        intersection: &BTreeSet<Name>,
+        lockfile_counts: &[usize],
+    ) -> Result<(), String> {
+        let semver_compatible = self.states[0].semver_compatible;
+        let mut all_ok = true;
+        let mut package_jsons = Vec::new();
+        for name in intersection.iter() {
+            // One slot per lockfile, but a slot can hold more than one entry
+            // when that lockfile resolves `name` to more than one source -
+            // keep every variant instead of arbitrarily picking the first.
+            let per_lockfile = self
+                .states
+                .iter()
+                .map(|state| entries_for_name(state, name))
+                .collect::<Vec<_>>();
+            let multi_source = per_lockfile.iter().any(|entries| entries.len() > 1);
+            let canonical = per_lockfile
+                .iter()
+                .map(|entries| entries[0])
+                .collect::<Vec<_>>();
+            let first_version = &canonical[0].1.version;
+            let first_source = &canonical[0].0 .1;
+            let same = !multi_source
+                && canonical.iter().all(|(key, pkg, _)| {
+                    &key.1 == first_source
+                        && (&pkg.version == first_version
+                            || (semver_compatible
+                                && versions_compatible(&pkg.version, first_version)))
+                });
+            if !same {
+                all_ok = false;
+            }
+            let lockfiles = per_lockfile
+                .iter()
+                .map(|entries| entry_json(entries))
+                .collect::<Vec<_>>()
+                .join(",");
+            package_jsons.push(format!(
+                "{{\"name\":\"{}\",\"multi_source\":{},\"same\":{},\"lockfiles\":[{}]}}",
+                json_escape(name.as_str()),
+                multi_source,
+                same,
+                lockfiles
+            ));
+        }
+        let lockfile_counts = lockfile_counts
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        println!(
+            "{{\"lockfile_counts\":[{}],\"common_count\":{},\"packages\":[{}]}}",
+            lockfile_counts,
+            intersection.len(),
+            package_jsons.join(",")
+        );
+        if all_ok {
+            Ok(())
+        } else {
+            Err("Some packages have different versions".to_string())
+        }
+    }
 }
 
 fn main() -> Result<(), String> {